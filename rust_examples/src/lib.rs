@@ -1,5 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::parse::Parser;
 use syn::{Data, Expr, Fields, parse_macro_input};
 
 // Proedural Macros
@@ -17,34 +18,169 @@ pub fn reverse_exprs(input: TokenStream) -> TokenStream {
     output.into()
 }
 
-#[proc_macro_derive(MyDebug)]
+// darling-style `FromField` analogue for `#[my_debug(...)]`.
+#[derive(Default)]
+struct FieldOpts {
+    skip: bool,
+    rename: Option<String>,
+    format: Option<String>,
+}
+
+impl FieldOpts {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = FieldOpts::default();
+        for attr in attrs {
+            if !attr.path().is_ident("my_debug") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    opts.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    opts.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("format") {
+                    opts.format = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported my_debug attribute, expected `skip`, `rename` or `format`"))
+                }
+            })?;
+        }
+        Ok(opts)
+    }
+}
+
+// Container-level `#[my_debug(...)]` options, parsed from the `DeriveInput` itself rather
+// than a field. Currently just the `bound` escape hatch for generics inference gone wrong.
+#[derive(Default)]
+struct DeriveOpts {
+    bound: Option<String>,
+}
+
+impl DeriveOpts {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = DeriveOpts::default();
+        for attr in attrs {
+            if !attr.path().is_ident("my_debug") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    opts.bound = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported my_debug container attribute, expected `bound`"))
+                }
+            })?;
+        }
+        Ok(opts)
+    }
+}
+
+// Builds the `print!` call for one field from its already-parsed `FieldOpts`. Returns an
+// empty token stream for skipped fields.
+fn field_print_tokens(
+    opts: &FieldOpts,
+    default_label: String,
+    accessor: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if opts.skip {
+        return quote! {};
+    }
+    let label = opts.rename.clone().unwrap_or(default_label);
+    let format = opts.format.clone().unwrap_or_else(|| "{:?}".to_string());
+    let print_fmt = format!("{{}}: {}, ", format);
+    quote! {
+        print!(#print_fmt, #label, #accessor);
+    }
+}
+
+// Does `ty` mention `ident` anywhere (including through references, tuples, arrays or
+// generic arguments)? Used to infer which type parameters need a `Debug` bound.
+fn type_contains_ident(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.iter().any(|seg| {
+            if seg.ident == *ident && type_path.path.segments.len() == 1 {
+                return true;
+            }
+            match &seg.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| {
+                    matches!(arg, syn::GenericArgument::Type(t) if type_contains_ident(t, ident))
+                }),
+                _ => false,
+            }
+        }),
+        syn::Type::Reference(r) => type_contains_ident(&r.elem, ident),
+        syn::Type::Tuple(t) => t.elems.iter().any(|e| type_contains_ident(e, ident)),
+        syn::Type::Array(a) => type_contains_ident(&a.elem, ident),
+        syn::Type::Slice(s) => type_contains_ident(&s.elem, ident),
+        syn::Type::Paren(p) => type_contains_ident(&p.elem, ident),
+        syn::Type::Group(g) => type_contains_ident(&g.elem, ident),
+        _ => false,
+    }
+}
+
+fn mark_used_params(ty: &syn::Type, type_params: &[syn::Ident], used: &mut Vec<syn::Ident>) {
+    for tp in type_params {
+        if !used.contains(tp) && type_contains_ident(ty, tp) {
+            used.push(tp.clone());
+        }
+    }
+}
+
+#[proc_macro_derive(MyDebug, attributes(my_debug))]
 pub fn my_debug_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
     let name = &ast.ident;
+    let mut errors: Vec<syn::Error> = Vec::new();
+    let type_params: Vec<syn::Ident> = ast
+        .generics
+        .type_params()
+        .map(|tp| tp.ident.clone())
+        .collect();
+    let mut used_params: Vec<syn::Ident> = Vec::new();
 
-    let code = match ast.data {
+    let body = match ast.data {
         Data::Struct(data_struct) => {
             let fields = match data_struct.fields {
                 Fields::Named(fields) => fields
                     .named
                     .iter()
                     .map(|f| {
-                        let field_name = &f.ident;
-                        quote! {
-                            print!("{}: {:?}, ", stringify!(#field_name), self.#field_name);
+                        let field_name = f.ident.as_ref().unwrap();
+                        let opts = match FieldOpts::from_attrs(&f.attrs) {
+                            Ok(opts) => opts,
+                            Err(err) => {
+                                errors.push(err);
+                                return quote! {};
+                            }
+                        };
+                        if !opts.skip {
+                            mark_used_params(&f.ty, &type_params, &mut used_params);
                         }
+                        field_print_tokens(&opts, field_name.to_string(), quote! { self.#field_name })
                     })
                     .collect::<Vec<_>>(),
                 Fields::Unnamed(fields) => fields
                     .unnamed
                     .iter()
                     .enumerate()
-                    .map(|(i, _f)| {
+                    .map(|(i, f)| {
                         let index = syn::Index::from(i);
-                        quote! {
-                            print!("{}: {:?}, ", #i, self.#index);
+                        let opts = match FieldOpts::from_attrs(&f.attrs) {
+                            Ok(opts) => opts,
+                            Err(err) => {
+                                errors.push(err);
+                                return quote! {};
+                            }
+                        };
+                        if !opts.skip {
+                            mark_used_params(&f.ty, &type_params, &mut used_params);
                         }
+                        field_print_tokens(&opts, i.to_string(), quote! { self.#index })
                     })
                     .collect::<Vec<_>>(),
                 Fields::Unit => {
@@ -52,24 +188,166 @@ pub fn my_debug_derive(input: TokenStream) -> TokenStream {
                 }
             };
             quote! {
-                impl MyDebug for #name {
-                    fn my_fmt(&self) {
-                        print!("{}: {{ ", stringify!(#name));
-                        #(#fields)*
-                        println!("}}");
+                print!("{}: {{ ", stringify!(#name));
+                #(#fields)*
+                println!("}}");
+            }
+        }
+        Data::Enum(data_enum) => {
+            let arms = data_enum
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    match &variant.fields {
+                        Fields::Named(fields) => {
+                            let idents: Vec<_> = fields
+                                .named
+                                .iter()
+                                .map(|f| f.ident.clone().unwrap())
+                                .collect();
+                            let prints = fields
+                                .named
+                                .iter()
+                                .zip(idents.iter())
+                                .map(|(f, field_name)| {
+                                    let opts = match FieldOpts::from_attrs(&f.attrs) {
+                                        Ok(opts) => opts,
+                                        Err(err) => {
+                                            errors.push(err);
+                                            return quote! {};
+                                        }
+                                    };
+                                    if !opts.skip {
+                                        mark_used_params(&f.ty, &type_params, &mut used_params);
+                                    }
+                                    field_print_tokens(&opts, field_name.to_string(), quote! { #field_name })
+                                })
+                                .collect::<Vec<_>>();
+                            quote! {
+                                #name::#variant_name { #(#idents),* } => {
+                                    print!("{}: {{ ", stringify!(#variant_name));
+                                    #(#prints)*
+                                    println!("}}");
+                                }
+                            }
+                        }
+                        Fields::Unnamed(fields) => {
+                            let binders: Vec<_> = (0..fields.unnamed.len())
+                                .map(|i| quote::format_ident!("__{}", i))
+                                .collect();
+                            let prints = fields
+                                .unnamed
+                                .iter()
+                                .zip(binders.iter())
+                                .enumerate()
+                                .map(|(i, (f, binder))| {
+                                    let opts = match FieldOpts::from_attrs(&f.attrs) {
+                                        Ok(opts) => opts,
+                                        Err(err) => {
+                                            errors.push(err);
+                                            return quote! {};
+                                        }
+                                    };
+                                    if !opts.skip {
+                                        mark_used_params(&f.ty, &type_params, &mut used_params);
+                                    }
+                                    field_print_tokens(&opts, i.to_string(), quote! { #binder })
+                                })
+                                .collect::<Vec<_>>();
+                            quote! {
+                                #name::#variant_name(#(#binders),*) => {
+                                    print!("{}: {{ ", stringify!(#variant_name));
+                                    #(#prints)*
+                                    println!("}}");
+                                }
+                            }
+                        }
+                        Fields::Unit => {
+                            quote! {
+                                #name::#variant_name => {
+                                    println!("{}: (unit variant)", stringify!(#variant_name));
+                                }
+                            }
+                        }
                     }
+                })
+                .collect::<Vec<_>>();
+            quote! {
+                match self {
+                    #(#arms)*
                 }
             }
         }
-        _ => {
-            panic!("MyDebug can only be derived for structs");
+        Data::Union(_) => {
+            panic!("MyDebug can only be derived for structs and enums");
         }
     };
-    code.into()
+
+    if let Some(combined) = errors.into_iter().reduce(|mut acc, err| {
+        acc.combine(err);
+        acc
+    }) {
+        return combined.to_compile_error().into();
+    }
+
+    let derive_opts = match DeriveOpts::from_attrs(&ast.attrs) {
+        Ok(opts) => opts,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let where_tokens = if let Some(bound) = derive_opts.bound {
+        match syn::parse_str::<syn::WhereClause>(&format!("where {bound}")) {
+            Ok(wc) => quote! { #wc },
+            Err(err) => return err.to_compile_error().into(),
+        }
+    } else {
+        let mut predicates: Vec<proc_macro2::TokenStream> = where_clause
+            .map(|wc| wc.predicates.iter().map(|p| quote! { #p }).collect())
+            .unwrap_or_default();
+        predicates.extend(
+            used_params
+                .iter()
+                .map(|param| quote! { #param: std::fmt::Debug }),
+        );
+        if predicates.is_empty() {
+            quote! {}
+        } else {
+            quote! { where #(#predicates),* }
+        }
+    };
+
+    quote! {
+        impl #impl_generics MyDebug for #name #ty_generics #where_tokens {
+            fn my_fmt(&self) {
+                #body
+            }
+        }
+    }
+    .into()
 }
 
 #[proc_macro_attribute]
-pub fn log_call(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn log_call(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let skip_args = if attr.is_empty() {
+        false
+    } else {
+        match syn::parse::<syn::Ident>(attr) {
+            Ok(ident) if ident == "skip_args" => true,
+            Ok(ident) => {
+                return syn::Error::new(
+                    ident.span(),
+                    "unsupported log_call attribute, expected `skip_args`",
+                )
+                .to_compile_error()
+                .into();
+            }
+            Err(err) => return err.to_compile_error().into(),
+        }
+    };
+
     let input_fn = parse_macro_input!(item as syn::ItemFn);
 
     let fn_name = &input_fn.sig.ident;
@@ -78,15 +356,92 @@ pub fn log_call(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let fn_vis = &input_fn.vis;
     let fn_attrs = &input_fn.attrs;
 
+    let arg_prints: Vec<_> = if skip_args {
+        Vec::new()
+    } else {
+        input_fn
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(pat_ident) => {
+                        let arg_ident = &pat_ident.ident;
+                        Some(quote! {
+                            println!("  {} = {:?}", stringify!(#arg_ident), #arg_ident);
+                        })
+                    }
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect()
+    };
+
+    // Exit logging lives in `Drop` so it still runs on unwind.
     let expanded = quote! {
         #(#fn_attrs)*
         #fn_vis #fn_sig {
+            struct __LogCallGuard {
+                name: &'static str,
+                start: std::time::Instant,
+            }
+            impl Drop for __LogCallGuard {
+                fn drop(&mut self) {
+                    println!(
+                        "Function `{}` returned (took {:?})",
+                        self.name,
+                        self.start.elapsed()
+                    );
+                }
+            }
+
             println!("Calling function `{}`", stringify!(#fn_name));
+            #(#arg_prints)*
+            let __log_call_guard = __LogCallGuard {
+                name: stringify!(#fn_name),
+                start: std::time::Instant::now(),
+            };
             let result = #fn_block;
-            println!("Function `{}` returned", stringify!(#fn_name));
+            let _ = &__log_call_guard;
             result
         }
     };
 
     expanded.into()
 }
+
+// Passes the item through unchanged; only side effect is the eprintln!/file dump below.
+#[proc_macro_attribute]
+pub fn dbg_expand(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut file: Option<String> = None;
+    if !attr.is_empty() {
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("file") {
+                file = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported dbg_expand attribute, expected `file`"))
+            }
+        });
+        if let Err(err) = parser.parse(attr) {
+            return err.to_compile_error().into();
+        }
+    }
+
+    let parsed: syn::File = match syn::parse(item.clone()) {
+        Ok(parsed) => parsed,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let pretty = prettyplease::unparse(&parsed);
+
+    eprintln!("// dbg_expand -----------------------------------\n{pretty}");
+
+    if let Some(path) = &file {
+        if let Err(err) = std::fs::write(path, &pretty) {
+            eprintln!("dbg_expand: failed to write `{path}`: {err}");
+        }
+    }
+
+    item
+}
@@ -112,13 +112,48 @@ macro_rules! vector {
     });
 }
 
+// `match_enumerate!(foo; A, B, C)` expands to `match foo { A => 0, B => 1, C => 2 }`, with an
+// optional `start N, step N` prefix. The counter is threaded through an accumulator arg rather
+// than mutated, since `macro_rules!` has no variables.
+macro_rules! match_enumerate {
+    ($e:expr; start $start:expr, step $step:expr; $($rest:tt)+) => {
+        match_enumerate!(@munch $e; $start; $step; []; $($rest)+)
+    };
+    ($e:expr; $($rest:tt)+) => {
+        match_enumerate!(@munch $e; 0; 1; []; $($rest)+)
+    };
+
+    // Catch-all terminal: `_ => $default` ends the muncher without numbering a pattern.
+    (@munch $e:expr; $curr:expr; $step:expr; [$($arms:tt)*]; _ => $default:expr $(,)?) => {
+        match $e {
+            $($arms)*
+            _ => $default,
+        }
+    };
+
+    // Base case: one bare pattern left, close out the match.
+    (@munch $e:expr; $curr:expr; $step:expr; [$($arms:tt)*]; $pat:pat $(,)?) => {
+        match $e {
+            $($arms)*
+            $pat => $curr,
+        }
+    };
+
+    // Recursive case: peel off the first pattern, recurse on the rest with `$curr + $step`.
+    (@munch $e:expr; $curr:expr; $step:expr; [$($arms:tt)*]; $pat:pat, $($rest:tt)+) => {
+        match_enumerate!(@munch $e; $curr + $step; $step; [$($arms)* $pat => $curr,]; $($rest)+)
+    };
+}
+
 pub trait MyDebug {
     fn my_fmt(&self);
 }
 
 #[derive(MyDebug)]
 struct Point {
+    #[my_debug(rename = "x_coord")]
     x: i32,
+    #[my_debug(skip)]
     y: i32,
 }
 
@@ -128,11 +163,36 @@ struct Coords(f64, f64);
 #[derive(MyDebug)]
 struct Empty;
 
+#[derive(MyDebug)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(MyDebug)]
+enum Shape {
+    Circle {
+        #[my_debug(format = "{:.2}")]
+        radius: f64,
+    },
+    Rectangle(f64, f64),
+    Point,
+}
+
 #[log_call]
 pub fn calculate_sum(a: i32, b: i32) -> i32 {
     a + b
 }
 
+#[log_call(skip_args)]
+pub fn expensive_closure(f: impl Fn() -> i32) -> i32 {
+    f()
+}
+
+#[dbg_expand]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
 // use : cargo expand --bin rust_examples
 fn main() {
     say_hello!();
@@ -177,5 +237,28 @@ fn main() {
     let e = Empty;
     e.my_fmt();
 
+    let circle = Shape::Circle { radius: 2.5 };
+    circle.my_fmt();
+
+    let rect = Shape::Rectangle(3.0, 4.0);
+    rect.my_fmt();
+
+    let point = Shape::Point;
+    point.my_fmt();
+
+    let wrapper = Wrapper { inner: 42 };
+    wrapper.my_fmt();
+
     calculate_sum(3, 9);
+    expensive_closure(|| 7);
+
+    let n = 2;
+    let rank = match_enumerate!(n; 1, 2, 3, _ => -1);
+    println!("rank = {}", rank);
+
+    let m = 4;
+    let rank2 = match_enumerate!(m; start 2, step 2; 1, 2, 4, _ => -1);
+    println!("rank2 = {}", rank2);
+
+    println!("{}", greet("world"));
 }